@@ -1,16 +1,27 @@
 // Built-in deps
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 // External imports
+use bigdecimal::BigDecimal;
 use diesel::prelude::*;
 use itertools::Itertools;
+use num_traits::FromPrimitive;
 // Workspace imports
 use models::node::{mempool::TxVariant, tx::TxHash, FranklinTx};
 // Local imports
-use self::records::{MempoolBatchBinding, MempoolTx, NewMempoolTx};
+use self::records::{
+    MempoolBatchBinding, MempoolConfig, MempoolTx, MempoolTxStatus, NewMempoolBatchBinding,
+    NewMempoolTx,
+};
 use crate::{schema::*, StorageProcessor};
 
 pub mod records;
 
+/// Weight applied to a resident's age (in hours) when computing its eviction
+/// score, so that an old, low-fee tx isn't instantly squeezed out by a
+/// marginally higher fee newcomer (a gas-price-style pending queue favors
+/// "first in" under a tie).
+const MEMPOOL_AGE_WEIGHT: f64 = 0.01;
+
 /// Schema for persisting transactions awaiting for the execution.
 ///
 /// This schema holds the transactions that are received by the `mempool` module, but not yet have
@@ -67,38 +78,513 @@ impl<'a> MempoolSchema<'a> {
         Ok(txs)
     }
 
+    /// Loads up to `limit` transactions ordered by priority instead of insertion order,
+    /// so block producers pick up the most valuable executable transactions first.
+    ///
+    /// Transactions are scored by fee, with ties broken by age. Batched `TxVariant::Batch`
+    /// groups are scored as a single unit (summed fee, oldest member's age) and always
+    /// returned contiguous. Per-account nonce ordering is preserved: an earlier nonce
+    /// inherits the highest score among its same-account successors, so a higher-fee
+    /// later-nonce transaction can never be surfaced ahead of a not-yet-executable one
+    /// (the same trick a gas-price-ordered pending queue uses).
+    pub fn load_txs_ready(&self, limit: usize) -> Result<Vec<TxVariant>, failure::Error> {
+        // Only `pending` txs are eligible: anything `in_progress` is already claimed by
+        // an executor (see `claim_txs`), and handing it out again here would mean two
+        // executors processing the same transaction at once.
+        let query = "SELECT * FROM mempool_txs \
+                     LEFT JOIN mempool_batch_binding ON mempool_txs.id = mempool_tx_id
+                     WHERE mempool_txs.status = 'pending'
+                     ORDER BY mempool_txs.id";
+
+        let rows: Vec<(MempoolTx, Option<MempoolBatchBinding>)> =
+            diesel::sql_query(query).load(self.0.conn())?;
+
+        // One entry per standalone transaction, or one entry per batch (all of its
+        // rows merged together), regardless of how the rows happen to be interleaved.
+        let mut entries: Vec<ReadyEntry> = Vec::new();
+        let mut batch_entry_idx: HashMap<i64, usize> = HashMap::new();
+
+        for (tx, batch) in rows {
+            match batch.map(|binding| binding.batch_id) {
+                Some(batch_id) => {
+                    if let Some(&idx) = batch_entry_idx.get(&batch_id) {
+                        entries[idx].own_score = entries[idx].own_score.clone() + tx_fee(&tx.tx);
+                        entries[idx].rows.push(tx);
+                    } else {
+                        batch_entry_idx.insert(batch_id, entries.len());
+                        entries.push(ReadyEntry::new(tx, Some(batch_id)));
+                    }
+                }
+                None => entries.push(ReadyEntry::new(tx, None)),
+            }
+        }
+
+        // Propagate each account's highest successor score back onto its earlier nonces.
+        let own_scores: Vec<BigDecimal> = entries.iter().map(|entry| entry.own_score.clone()).collect();
+        let account_nonces: Vec<Option<(String, i64)>> =
+            entries.iter().map(|entry| entry.account_nonce.clone()).collect();
+        let priority = propagate_nonce_priority(&own_scores, &account_nonces);
+
+        // `sort_by` is stable and entries are in insertion order, so equal priorities
+        // keep the older entry first (the age tie-break).
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by(|&a, &b| priority[b].cmp(&priority[a]));
+
+        let mut ready = Vec::with_capacity(limit.min(entries.len()));
+        for idx in order.into_iter().take(limit) {
+            ready.push(entries[idx].clone().into_variant()?);
+        }
+
+        Ok(ready)
+    }
+
+    /// Re-adds the transactions of a block that failed verification (or was reverted)
+    /// back into the mempool, so that a malicious or simply incorrect block can't
+    /// silently wipe out otherwise-valid transactions. Transactions that are already
+    /// committed (e.g. a double-spend that got included in a later block) are skipped.
+    ///
+    /// `local_tx_hashes` is the set of (hex-encoded) hashes of transactions that were
+    /// originally submitted through this node's own API. A transaction's `local` status
+    /// lives only in the now-deleted `mempool_txs` row, so the caller must supply it
+    /// (e.g. from a `load_local_txs()` snapshot taken before the block was extracted) —
+    /// otherwise a reinserted local transaction would silently lose its eviction
+    /// protection.
+    pub fn reinsert(
+        &self,
+        txs: &[TxVariant],
+        local_tx_hashes: &std::collections::HashSet<String>,
+    ) -> Result<(), failure::Error> {
+        for variant in txs {
+            match variant {
+                TxVariant::Tx(tx) => {
+                    let local = local_tx_hashes.contains(&hex::encode(tx.hash().as_ref()));
+                    self.reinsert_tx(tx, local)?;
+                }
+                TxVariant::Batch(batch) => {
+                    let local = batch
+                        .first()
+                        .map(|tx| local_tx_hashes.contains(&hex::encode(tx.hash().as_ref())))
+                        .unwrap_or(false);
+                    self.reinsert_batch(batch, local)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_committed(&self, tx: &FranklinTx) -> Result<bool, failure::Error> {
+        let tx_hash = tx.hash();
+        let committed = self
+            .0
+            .chain()
+            .operations_ext_schema()
+            .get_tx_by_hash(tx_hash.as_ref())?
+            .is_some();
+
+        Ok(committed)
+    }
+
+    fn reinsert_tx(&self, tx: &FranklinTx, local: bool) -> Result<(), failure::Error> {
+        if self.is_committed(tx)? {
+            return Ok(());
+        }
+
+        self.insert_tx_inner(tx, local)
+    }
+
+    fn reinsert_batch(&self, txs: &[FranklinTx], local: bool) -> Result<(), failure::Error> {
+        let mut pending = Vec::with_capacity(txs.len());
+        for tx in txs {
+            if !self.is_committed(tx)? {
+                pending.push(tx);
+            }
+        }
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        self.0.conn().transaction(|| {
+            let batch_id = self.next_batch_id()?;
+
+            for tx in pending {
+                let tx_hash = hex::encode(tx.hash().as_ref());
+                let tx_json = serde_json::to_value(tx)?;
+                let tx_bytes = tx_json.to_string().len() as i64;
+
+                let db_entry = NewMempoolTx {
+                    tx_hash,
+                    tx: tx_json,
+                    tx_bytes,
+                    local,
+                };
+
+                let mempool_tx_id: i64 = diesel::insert_into(mempool_txs::table)
+                    .values(&db_entry)
+                    .returning(mempool_txs::id)
+                    .get_result(self.0.conn())?;
+
+                let binding = NewMempoolBatchBinding {
+                    mempool_tx_id,
+                    batch_id,
+                };
+
+                diesel::insert_into(mempool_batch_binding::table)
+                    .values(&binding)
+                    .execute(self.0.conn())?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Picks a fresh batch id for a group of transactions being (re)inserted together.
+    ///
+    /// Drawn from the `mempool_batch_id_seq` Postgres sequence rather than a
+    /// read-then-increment `MAX(batch_id) + 1`, so two concurrent callers (e.g. two
+    /// reverted blocks processed around the same time) can never be handed the same id
+    /// and silently merge two unrelated batches together.
+    fn next_batch_id(&self) -> QueryResult<i64> {
+        #[derive(QueryableByName)]
+        struct NextVal {
+            #[sql_type = "diesel::sql_types::BigInt"]
+            nextval: i64,
+        }
+
+        let result: NextVal =
+            diesel::sql_query("SELECT nextval('mempool_batch_id_seq') AS nextval")
+                .get_result(self.0.conn())?;
+
+        Ok(result.nextval)
+    }
+
     /// Adds a new transaction to the mempool schema.
+    ///
+    /// If the mempool is at capacity (see `set_capacity`), the incoming transaction is
+    /// only accepted if it outranks the lowest-ranked resident, which is evicted to make
+    /// room. Ranking combines the transaction's fee with its age, so the mempool can't be
+    /// starved by a constant stream of marginally-higher-fee transactions.
     pub fn insert_tx(&self, tx_data: &FranklinTx) -> Result<(), failure::Error> {
+        self.insert_tx_inner(tx_data, false)
+    }
+
+    /// Adds a transaction that originated from this node's own API. Local transactions
+    /// are never chosen as eviction victims by the capacity logic above, so a node under
+    /// load won't drop the very users it's directly serving.
+    pub fn insert_local_tx(&self, tx_data: &FranklinTx) -> Result<(), failure::Error> {
+        self.insert_tx_inner(tx_data, true)
+    }
+
+    /// Returns the transactions this node accepted directly (as opposed to relayed or
+    /// gossiped from peers), in insertion order.
+    pub fn load_local_txs(&self) -> Result<Vec<FranklinTx>, failure::Error> {
+        let rows = mempool_txs::table
+            .filter(mempool_txs::local.eq(true))
+            .order(mempool_txs::id)
+            .load::<MempoolTx>(self.0.conn())?;
+
+        rows.into_iter()
+            .map(|row| serde_json::from_value(row.tx).map_err(From::from))
+            .collect()
+    }
+
+    /// Runs the capacity check/eviction and the insert itself in one DB transaction, so
+    /// a crash in between can't leave the pool short a tx, and two concurrent inserts
+    /// can't both observe "room available" and overshoot `max_size`.
+    fn insert_tx_inner(&self, tx_data: &FranklinTx, local: bool) -> Result<(), failure::Error> {
         let tx_hash = hex::encode(tx_data.hash().as_ref());
         let tx = serde_json::to_value(tx_data)?;
+        let tx_bytes = tx.to_string().len() as i64;
+        let incoming_score = tx_fee(&tx);
+
+        self.0.conn().transaction(|| {
+            // Lock the (singleton) capacity row first: `COUNT(*)` can't itself take a
+            // row lock (Postgres rejects `FOR UPDATE` alongside an aggregate), so
+            // without this, two concurrent inserts could both read "room available"
+            // under READ COMMITTED and both proceed, overshooting `max_size`. Locking
+            // this row serializes every capacity-bounded insert behind it instead.
+            if let Some(max_size) = self.lock_capacity()? {
+                if local {
+                    self.make_room_for_local(max_size)?;
+                } else if !self.evict_if_full(max_size, &incoming_score)? {
+                    // The pool is full and the incoming tx doesn't outrank the weakest
+                    // resident: drop it rather than let the pool grow unbounded.
+                    return Ok(());
+                }
+            }
+
+            let db_entry = NewMempoolTx {
+                tx_hash,
+                tx,
+                tx_bytes,
+                local,
+            };
+
+            diesel::insert_into(mempool_txs::table)
+                .values(db_entry)
+                .execute(self.0.conn())?;
 
-        let db_entry = NewMempoolTx { tx_hash, tx };
+            Ok(())
+        })
+    }
+
+    /// Sets the maximum number of transactions the mempool is allowed to hold.
+    /// Pass `None` to remove the bound (the historical, unbounded behavior).
+    pub fn set_capacity(&self, max_size: Option<i64>) -> QueryResult<()> {
+        let config = MempoolConfig { id: 1, max_size };
 
-        diesel::insert_into(mempool_txs::table)
-            .values(db_entry)
+        diesel::insert_into(mempool_config::table)
+            .values(&config)
+            .on_conflict(mempool_config::id)
+            .do_update()
+            .set(&config)
             .execute(self.0.conn())?;
 
         Ok(())
     }
 
+    /// Returns the currently configured mempool capacity, if any.
+    pub fn get_capacity(&self) -> QueryResult<Option<i64>> {
+        let config = mempool_config::table
+            .find(1i16)
+            .first::<MempoolConfig>(self.0.conn())
+            .optional()?;
+
+        Ok(config.and_then(|config| config.max_size))
+    }
+
+    /// Same as `get_capacity`, but takes a row lock on the `mempool_config` entry, so
+    /// concurrent capacity-bounded inserts serialize on this read instead of racing
+    /// each other to the same "room available" conclusion.
+    fn lock_capacity(&self) -> QueryResult<Option<i64>> {
+        let config = mempool_config::table
+            .find(1i16)
+            .for_update()
+            .first::<MempoolConfig>(self.0.conn())
+            .optional()?;
+
+        Ok(config.and_then(|config| config.max_size))
+    }
+
+    /// Makes room for one more transaction if the mempool is at (or over) `max_size`.
+    ///
+    /// Returns `true` if there is (now) room for the incoming transaction, and `false`
+    /// if the mempool is full and the incoming transaction doesn't outrank the
+    /// lowest-ranked resident (in which case nothing is evicted).
+    fn evict_if_full(&self, max_size: i64, incoming_score: &BigDecimal) -> QueryResult<bool> {
+        let current_size = mempool_txs::table.count().get_result::<i64>(self.0.conn())?;
+
+        if current_size < max_size {
+            return Ok(true);
+        }
+
+        match self.weakest_victim()? {
+            Some(victim) if *incoming_score > mempool_tx_score(&victim) => {
+                diesel::delete(mempool_txs::table.filter(mempool_txs::id.eq(victim.id)))
+                    .execute(self.0.conn())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Makes room for a local transaction if the mempool is at (or over) `max_size`,
+    /// evicting the lowest-ranked *non-local*, pending resident unconditionally (a local
+    /// transaction is never rejected on rank grounds). If every resident is local or
+    /// `in_progress`, there's nothing safe to evict, and the pool temporarily exceeds
+    /// `max_size` rather than drop a locally-submitted transaction.
+    fn make_room_for_local(&self, max_size: i64) -> QueryResult<()> {
+        let current_size = mempool_txs::table.count().get_result::<i64>(self.0.conn())?;
+
+        if current_size < max_size {
+            return Ok(());
+        }
+
+        if let Some(victim) = self.weakest_victim()? {
+            diesel::delete(mempool_txs::table.filter(mempool_txs::id.eq(victim.id)))
+                .execute(self.0.conn())?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the lowest-ranked evictable resident, if any. Local transactions are
+    /// excluded so they can never be chosen as eviction victims, and `in_progress`
+    /// transactions are excluded too — one is already claimed by an executor (see
+    /// `claim_txs`), and evicting it here would delete the row out from under the
+    /// in-flight block, with no way for `requeue_stale` to ever recover it.
+    fn weakest_victim(&self) -> QueryResult<Option<MempoolTx>> {
+        let residents = mempool_txs::table
+            .filter(mempool_txs::local.eq(false))
+            .filter(mempool_txs::status.eq(MempoolTxStatus::Pending))
+            .for_update()
+            .load::<MempoolTx>(self.0.conn())?;
+
+        Ok(residents
+            .into_iter()
+            .min_by(|a, b| mempool_tx_score(a).partial_cmp(&mempool_tx_score(b)).unwrap()))
+    }
+
+    /// Returns the total size, in bytes, of all transactions currently stored in the
+    /// mempool. This is always recomputed from the `tx_bytes` column rather than kept
+    /// as a running counter, so it can never drift from what's actually on disk, even
+    /// if a previous `insert_tx`/`remove_tx`/`remove_txs` call only partially completed.
+    pub fn total_bytes(&self) -> QueryResult<i64> {
+        let total: Option<i64> = mempool_txs::table
+            .select(diesel::dsl::sum(mempool_txs::tx_bytes))
+            .first(self.0.conn())?;
+
+        Ok(total.unwrap_or(0))
+    }
+
+    /// Atomically marks up to `limit` pending transactions as `in_progress` and returns
+    /// them, stamping a `claimed_at` heartbeat. This is the hand-off point to the
+    /// executor: once claimed, a transaction won't be handed to another caller of
+    /// `claim_txs` unless it's first reclaimed via `requeue_stale`.
+    ///
+    /// Claiming is batch-aware: if any candidate belongs to a `TxVariant::Batch`, every
+    /// other still-pending member of that batch is pulled in too, so a batch is always
+    /// handed to the executor whole (possibly pushing the result slightly past `limit`)
+    /// rather than claimed piecemeal.
+    pub fn claim_txs(&self, limit: i64) -> QueryResult<Vec<MempoolTx>> {
+        self.0.conn().transaction(|| {
+            let candidate_ids: Vec<i64> = mempool_txs::table
+                .filter(mempool_txs::status.eq(MempoolTxStatus::Pending))
+                .order(mempool_txs::id)
+                .limit(limit)
+                .select(mempool_txs::id)
+                .for_update()
+                .load(self.0.conn())?;
+
+            let candidate_batches: Vec<i64> = mempool_batch_binding::table
+                .filter(mempool_batch_binding::mempool_tx_id.eq_any(&candidate_ids))
+                .select(mempool_batch_binding::batch_id)
+                .distinct()
+                .load(self.0.conn())?;
+
+            let mut ids: std::collections::HashSet<i64> = candidate_ids.into_iter().collect();
+            if !candidate_batches.is_empty() {
+                let batch_member_ids: Vec<i64> = mempool_batch_binding::table
+                    .inner_join(mempool_txs::table)
+                    .filter(mempool_batch_binding::batch_id.eq_any(&candidate_batches))
+                    .filter(mempool_txs::status.eq(MempoolTxStatus::Pending))
+                    .select(mempool_batch_binding::mempool_tx_id)
+                    .for_update()
+                    .load(self.0.conn())?;
+
+                ids.extend(batch_member_ids);
+            }
+
+            let ids: Vec<i64> = ids.into_iter().collect();
+
+            diesel::update(mempool_txs::table.filter(mempool_txs::id.eq_any(&ids)))
+                .set((
+                    mempool_txs::status.eq(MempoolTxStatus::InProgress),
+                    mempool_txs::claimed_at.eq(diesel::dsl::now),
+                ))
+                .execute(self.0.conn())?;
+
+            mempool_txs::table
+                .filter(mempool_txs::id.eq_any(&ids))
+                .order(mempool_txs::id)
+                .load(self.0.conn())
+        })
+    }
+
+    /// Flips `in_progress` transactions that have been claimed for longer than `timeout`
+    /// back to `pending`, so a crashed executor's in-flight work isn't lost forever.
+    /// Returns the number of transactions requeued.
+    pub fn requeue_stale(&self, timeout: chrono::Duration) -> QueryResult<usize> {
+        let stale_before = chrono::Utc::now().naive_utc() - timeout;
+
+        diesel::update(
+            mempool_txs::table.filter(
+                mempool_txs::status
+                    .eq(MempoolTxStatus::InProgress)
+                    .and(mempool_txs::claimed_at.lt(stale_before)),
+            ),
+        )
+        .set((
+            mempool_txs::status.eq(MempoolTxStatus::Pending),
+            mempool_txs::claimed_at.eq(None::<chrono::NaiveDateTime>),
+        ))
+        .execute(self.0.conn())
+    }
+
+    /// Removes a transaction from the mempool. If the transaction is part of a batch,
+    /// the whole batch (and its binding rows) is removed together, in one DB
+    /// transaction, so a committed batch never leaves dangling siblings behind.
     pub fn remove_tx(&self, tx: &[u8]) -> QueryResult<()> {
         let tx_hash = hex::encode(tx);
 
-        diesel::delete(mempool_txs::table.filter(mempool_txs::tx_hash.eq(&tx_hash)))
-            .execute(self.0.conn())?;
+        self.0.conn().transaction(|| {
+            let mempool_tx = mempool_txs::table
+                .filter(mempool_txs::tx_hash.eq(&tx_hash))
+                .first::<MempoolTx>(self.0.conn())
+                .optional()?;
+
+            let mempool_tx = match mempool_tx {
+                Some(mempool_tx) => mempool_tx,
+                None => return Ok(()),
+            };
 
-        // TODO: Check if there is a corresponding batch for the tx, and remove it as well if necessary.
+            let batch_binding = mempool_batch_binding::table
+                .filter(mempool_batch_binding::mempool_tx_id.eq(mempool_tx.id))
+                .first::<MempoolBatchBinding>(self.0.conn())
+                .optional()?;
+
+            match batch_binding {
+                Some(binding) => self.remove_batch(binding.batch_id),
+                None => {
+                    diesel::delete(mempool_txs::table.filter(mempool_txs::id.eq(mempool_tx.id)))
+                        .execute(self.0.conn())?;
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    /// Removes every transaction belonging to `batch_id`, along with its binding rows.
+    fn remove_batch(&self, batch_id: i64) -> QueryResult<()> {
+        let tx_ids: Vec<i64> = mempool_batch_binding::table
+            .filter(mempool_batch_binding::batch_id.eq(batch_id))
+            .select(mempool_batch_binding::mempool_tx_id)
+            .load(self.0.conn())?;
+
+        diesel::delete(
+            mempool_batch_binding::table.filter(mempool_batch_binding::batch_id.eq(batch_id)),
+        )
+        .execute(self.0.conn())?;
+
+        diesel::delete(mempool_txs::table.filter(mempool_txs::id.eq_any(tx_ids)))
+            .execute(self.0.conn())?;
 
         Ok(())
     }
 
+    /// Removes a set of transactions (identified by hash) along with any batch binding
+    /// rows that reference them, in one DB transaction, so a failure partway through
+    /// can't leave orphaned bindings behind.
     fn remove_txs(&self, txs: &[TxHash]) -> Result<(), failure::Error> {
         let tx_hashes: Vec<_> = txs.iter().map(hex::encode).collect();
 
-        diesel::delete(mempool_txs::table.filter(mempool_txs::tx_hash.eq_any(&tx_hashes)))
+        self.0.conn().transaction(|| {
+            let ids: Vec<i64> = mempool_txs::table
+                .filter(mempool_txs::tx_hash.eq_any(&tx_hashes))
+                .select(mempool_txs::id)
+                .load(self.0.conn())?;
+
+            diesel::delete(
+                mempool_batch_binding::table.filter(mempool_batch_binding::mempool_tx_id.eq_any(&ids)),
+            )
             .execute(self.0.conn())?;
 
-        Ok(())
+            diesel::delete(mempool_txs::table.filter(mempool_txs::id.eq_any(&ids)))
+                .execute(self.0.conn())?;
+
+            Ok(())
+        })
     }
 
     /// Removes transactions that are already committed.
@@ -123,9 +609,19 @@ impl<'a> MempoolSchema<'a> {
                         .expect("DB issue while restoring the mempool state")
                         .is_some()
                 }
-                TxVariant::Batch(_batch) => {
-                    // TODO
-                    unimplemented!()
+                TxVariant::Batch(batch) => {
+                    // A batch is garbage once every transaction it contains has been
+                    // committed; a partially-committed batch is left alone, since the
+                    // still-pending transactions within it are still executable.
+                    batch.iter().all(|tx| {
+                        let tx_hash = tx.hash();
+                        self.0
+                            .chain()
+                            .operations_ext_schema()
+                            .get_tx_by_hash(tx_hash.as_ref())
+                            .expect("DB issue while restoring the mempool state")
+                            .is_some()
+                    })
                 }
             }
         });
@@ -141,3 +637,220 @@ impl<'a> MempoolSchema<'a> {
         Ok(())
     }
 }
+
+/// Extracts the fee of a serialized transaction, defaulting to zero for variants that
+/// don't carry an explicit fee (e.g. priority operations), so they're never favored by
+/// the eviction logic.
+fn tx_fee(tx: &serde_json::Value) -> BigDecimal {
+    tx.get("fee")
+        .and_then(|fee| fee.as_str())
+        .and_then(|fee| fee.parse().ok())
+        .unwrap_or_else(BigDecimal::default)
+}
+
+/// Extracts the `(accountId, nonce)` pair of a serialized transaction, if it carries
+/// one. Priority operations (deposits, full exits) don't, and fall outside the
+/// per-account nonce ordering.
+fn tx_account_nonce(tx: &serde_json::Value) -> Option<(String, i64)> {
+    let account = match tx.get("accountId")? {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    let nonce = tx.get("nonce")?.as_i64()?;
+
+    Some((account, nonce))
+}
+
+/// Given each entry's own fee score and its `(accountId, nonce)` (if any),
+/// returns a parallel vector of priorities where an earlier nonce inherits the highest
+/// score among its same-account successors. Entries with no `account_nonce` (priority
+/// operations) keep their own score unchanged.
+///
+/// Pulled out of `load_txs_ready` as a pure function so the suffix-max propagation can
+/// be unit-tested without a live DB connection.
+fn propagate_nonce_priority(
+    own_scores: &[BigDecimal],
+    account_nonces: &[Option<(String, i64)>],
+) -> Vec<BigDecimal> {
+    let mut accounts: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, account_nonce) in account_nonces.iter().enumerate() {
+        if let Some((account, _)) = account_nonce {
+            accounts.entry(account.as_str()).or_default().push(idx);
+        }
+    }
+
+    let mut priority = own_scores.to_vec();
+    for idxs in accounts.values_mut() {
+        idxs.sort_by_key(|&idx| account_nonces[idx].as_ref().unwrap().1);
+
+        let mut inherited = BigDecimal::default();
+        for &idx in idxs.iter().rev() {
+            if priority[idx] > inherited {
+                inherited = priority[idx].clone();
+            }
+            priority[idx] = inherited.clone();
+        }
+    }
+
+    priority
+}
+
+/// A candidate for `load_txs_ready`: either a single transaction, or the rows of a
+/// whole batch (kept together so it's always returned as one contiguous unit).
+#[derive(Debug, Clone)]
+struct ReadyEntry {
+    rows: Vec<MempoolTx>,
+    batch_id: Option<i64>,
+    /// Fee of this entry alone (summed over a batch's rows), before per-account nonce
+    /// propagation.
+    own_score: BigDecimal,
+    /// `(accountId, nonce)` of the entry, taken from its first (earliest-inserted) row.
+    account_nonce: Option<(String, i64)>,
+}
+
+impl ReadyEntry {
+    fn new(tx: MempoolTx, batch_id: Option<i64>) -> Self {
+        let own_score = tx_fee(&tx.tx);
+        let account_nonce = tx_account_nonce(&tx.tx);
+
+        ReadyEntry {
+            rows: vec![tx],
+            batch_id,
+            own_score,
+            account_nonce,
+        }
+    }
+
+    fn into_variant(self) -> Result<TxVariant, failure::Error> {
+        let txs: Vec<FranklinTx> = self
+            .rows
+            .into_iter()
+            .map(|row| serde_json::from_value(row.tx).map_err(From::from))
+            .collect::<Result<_, failure::Error>>()?;
+
+        match self.batch_id {
+            Some(_) => Ok(TxVariant::from(txs)),
+            None => Ok(TxVariant::from(
+                txs.into_iter().next().expect("a ready entry always has a tx"),
+            )),
+        }
+    }
+}
+
+/// Eviction score for a stored transaction: its fee, bumped up the longer it's been
+/// sitting in the pool, so low-fee-but-old transactions aren't perpetually starved out
+/// by a steady stream of newer, marginally higher-fee transactions.
+fn mempool_tx_score(resident: &MempoolTx) -> BigDecimal {
+    let fee = tx_fee(&resident.tx);
+    let age_hours = (chrono::Utc::now().naive_utc() - resident.created_at).num_minutes() as f64 / 60.0;
+    let age_bonus = BigDecimal::from_f64(age_hours * MEMPOOL_AGE_WEIGHT).unwrap_or_default();
+
+    fee + age_bonus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn resident(fee: &str, age_hours: i64) -> MempoolTx {
+        MempoolTx {
+            id: 0,
+            tx_hash: String::new(),
+            tx: serde_json::json!({ "fee": fee }),
+            created_at: chrono::Utc::now().naive_utc() - chrono::Duration::hours(age_hours),
+            tx_bytes: 0,
+            status: MempoolTxStatus::Pending,
+            claimed_at: None,
+            local: false,
+        }
+    }
+
+    #[test]
+    fn tx_fee_reads_the_fee_field() {
+        let tx = serde_json::json!({ "fee": "42" });
+        assert_eq!(tx_fee(&tx), BigDecimal::from_str("42").unwrap());
+    }
+
+    #[test]
+    fn tx_fee_defaults_to_zero_when_absent() {
+        let tx = serde_json::json!({ "accountId": 1 });
+        assert_eq!(tx_fee(&tx), BigDecimal::default());
+    }
+
+    #[test]
+    fn tx_account_nonce_reads_numeric_and_string_account_ids() {
+        let numeric = serde_json::json!({ "accountId": 7, "nonce": 3 });
+        assert_eq!(tx_account_nonce(&numeric), Some(("7".to_string(), 3)));
+
+        let stringy = serde_json::json!({ "accountId": "7", "nonce": 3 });
+        assert_eq!(tx_account_nonce(&stringy), Some(("7".to_string(), 3)));
+    }
+
+    #[test]
+    fn tx_account_nonce_is_none_for_priority_operations() {
+        let tx = serde_json::json!({ "fee": "1" });
+        assert_eq!(tx_account_nonce(&tx), None);
+    }
+
+    #[test]
+    fn mempool_tx_score_favors_older_txs_at_equal_fee() {
+        let young = resident("10", 0);
+        let old = resident("10", 100);
+
+        assert!(mempool_tx_score(&old) > mempool_tx_score(&young));
+    }
+
+    #[test]
+    fn mempool_tx_score_favors_higher_fee_despite_age() {
+        let cheap_and_old = resident("1", 1);
+        let expensive_and_new = resident("1000", 0);
+
+        assert!(mempool_tx_score(&expensive_and_new) > mempool_tx_score(&cheap_and_old));
+    }
+
+    #[test]
+    fn nonce_priority_is_untouched_without_account_nonce() {
+        let scores = vec![BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("2").unwrap()];
+        let account_nonces = vec![None, None];
+
+        assert_eq!(propagate_nonce_priority(&scores, &account_nonces), scores);
+    }
+
+    #[test]
+    fn earlier_nonce_inherits_max_score_of_later_successors() {
+        // Same account, nonces 0, 1, 2 with scores 1, 5, 2: nonce 0 and 1 should both
+        // inherit 5 (the max of their successors), nonce 2 keeps its own score of 2.
+        let scores = vec![
+            BigDecimal::from_str("1").unwrap(),
+            BigDecimal::from_str("5").unwrap(),
+            BigDecimal::from_str("2").unwrap(),
+        ];
+        let account_nonces = vec![
+            Some(("acc".to_string(), 0)),
+            Some(("acc".to_string(), 1)),
+            Some(("acc".to_string(), 2)),
+        ];
+
+        let priority = propagate_nonce_priority(&scores, &account_nonces);
+
+        assert_eq!(priority[0], BigDecimal::from_str("5").unwrap());
+        assert_eq!(priority[1], BigDecimal::from_str("5").unwrap());
+        assert_eq!(priority[2], BigDecimal::from_str("2").unwrap());
+    }
+
+    #[test]
+    fn nonce_priority_does_not_leak_across_accounts() {
+        let scores = vec![BigDecimal::from_str("1").unwrap(), BigDecimal::from_str("99").unwrap()];
+        let account_nonces = vec![
+            Some(("acc-a".to_string(), 0)),
+            Some(("acc-b".to_string(), 0)),
+        ];
+
+        let priority = propagate_nonce_priority(&scores, &account_nonces);
+
+        assert_eq!(priority[0], BigDecimal::from_str("1").unwrap());
+        assert_eq!(priority[1], BigDecimal::from_str("99").unwrap());
+    }
+}
@@ -0,0 +1,63 @@
+// External imports
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel_derive_enum::DbEnum;
+// Workspace imports
+// Local imports
+use crate::schema::*;
+
+/// Processing state of a mempool transaction, backing the claim/heartbeat flow that
+/// protects against re-executing a tx that was already handed off before a crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, DbEnum)]
+#[DieselType = "MempoolTxStatusMapping"]
+pub enum MempoolTxStatus {
+    /// Not yet handed off to an executor.
+    Pending,
+    /// Claimed by an executor; see `claimed_at` for when.
+    InProgress,
+}
+
+#[derive(Debug, Clone, Queryable, QueryableByName)]
+#[table_name = "mempool_txs"]
+pub struct MempoolTx {
+    pub id: i64,
+    pub tx_hash: String,
+    pub tx: serde_json::Value,
+    pub created_at: NaiveDateTime,
+    pub tx_bytes: i64,
+    pub status: MempoolTxStatus,
+    pub claimed_at: Option<NaiveDateTime>,
+    pub local: bool,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "mempool_txs"]
+pub struct NewMempoolTx {
+    pub tx_hash: String,
+    pub tx: serde_json::Value,
+    pub tx_bytes: i64,
+    pub local: bool,
+}
+
+#[derive(Debug, Clone, Queryable, QueryableByName)]
+#[table_name = "mempool_batch_binding"]
+pub struct MempoolBatchBinding {
+    pub id: i64,
+    pub mempool_tx_id: i64,
+    pub batch_id: i64,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "mempool_batch_binding"]
+pub struct NewMempoolBatchBinding {
+    pub mempool_tx_id: i64,
+    pub batch_id: i64,
+}
+
+/// Singleton row holding the mempool capacity bound (`None` while unbounded).
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[table_name = "mempool_config"]
+pub struct MempoolConfig {
+    pub id: i16,
+    pub max_size: Option<i64>,
+}
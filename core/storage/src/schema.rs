@@ -0,0 +1,40 @@
+// This file is a (partial) mirror of the database schema, following the usual
+// `diesel print-schema` layout. Only the tables touched by the mempool schema
+// are reproduced here, since the rest of the schema lives outside this snapshot.
+
+table! {
+    use diesel::sql_types::*;
+    // `#[derive(DbEnum)]` on `MempoolTxStatus` is what generates this mapping type;
+    // it has to be imported from there, not redeclared here.
+    use crate::chain::mempool::records::MempoolTxStatusMapping;
+
+    mempool_txs (id) {
+        id -> BigInt,
+        tx_hash -> Text,
+        tx -> Jsonb,
+        created_at -> Timestamp,
+        tx_bytes -> BigInt,
+        status -> MempoolTxStatusMapping,
+        claimed_at -> Nullable<Timestamp>,
+        local -> Bool,
+    }
+}
+
+table! {
+    mempool_batch_binding (id) {
+        id -> BigInt,
+        mempool_tx_id -> BigInt,
+        batch_id -> BigInt,
+    }
+}
+
+table! {
+    mempool_config (id) {
+        id -> SmallInt,
+        max_size -> Nullable<BigInt>,
+    }
+}
+
+joinable!(mempool_batch_binding -> mempool_txs (mempool_tx_id));
+
+allow_tables_to_appear_in_same_query!(mempool_txs, mempool_batch_binding,);